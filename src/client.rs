@@ -0,0 +1,123 @@
+//! Sync/async split for driving matches.
+//!
+//! `SyncMatchClient` is just today's blocking `SnakeRuntime::run_game`. `AsyncMatchClient` lets
+//! a caller on a tokio runtime await a single seed or fold over a `Stream` of results as they
+//! complete, instead of joining raw `std::thread` handles. `PooledMatchClient` is the
+//! tokio-backed implementation: it owns a pool of `SnakeRuntime` instances and dispatches seeds
+//! across them with `spawn_blocking`, since a `Store` is not `Sync` and `run_game` is CPU-bound.
+//!
+//! No unit tests here: every constructor and every `runtime_for` dispatch goes through a real
+//! `SnakeRuntime`, which needs `SNAKE_RUNTIME_WASM` actually instantiated — there's no way to
+//! fake a pool entry without that engine, so this module is exercised by whatever integration
+//! coverage runs the CLI/server end to end instead.
+
+use std::{future::Future, ops::Range, pin::Pin, sync::Arc};
+
+use futures::{stream, Stream, StreamExt};
+
+use crate::{
+    validation::{RuleSet, ValidationError},
+    GameResult, SnakeRuntime,
+};
+
+/// Blocking, single-seed match driver.
+pub trait SyncMatchClient {
+    fn run_game(&mut self, seed: u32) -> GameResult;
+}
+
+impl SyncMatchClient for SnakeRuntime {
+    fn run_game(&mut self, seed: u32) -> GameResult {
+        SnakeRuntime::run_game(self, seed)
+    }
+}
+
+/// Non-blocking match driver for callers on a tokio runtime.
+pub trait AsyncMatchClient {
+    /// Runs a single seed, resolving once the game finishes.
+    fn run_game(&self, seed: u32) -> Pin<Box<dyn Future<Output = GameResult> + Send + '_>>;
+
+    /// Runs every seed in `seeds`, yielding each `GameResult` as soon as it completes rather
+    /// than in seed order.
+    fn run_games(&self, seeds: Range<u32>) -> Pin<Box<dyn Stream<Item = GameResult> + Send + '_>>;
+}
+
+/// Both driving modes on one handle.
+pub trait MatchClient: SyncMatchClient + AsyncMatchClient {}
+impl<T: SyncMatchClient + AsyncMatchClient> MatchClient for T {}
+
+/// A `MatchClient` that owns a fixed pool of `SnakeRuntime` instances and round-robins seeds
+/// across them.
+pub struct PooledMatchClient {
+    pool: Vec<Arc<std::sync::Mutex<SnakeRuntime>>>,
+}
+
+impl PooledMatchClient {
+    /// Builds a pool of `pool_size` runtimes (at least one), each loaded with the same pair of
+    /// player WASM blobs. Fails if either player module doesn't pass the default `RuleSet`.
+    pub fn new(red: &[u8], blue: &[u8], pool_size: usize) -> Result<Self, ValidationError> {
+        Self::with_limits(
+            red,
+            blue,
+            pool_size,
+            &RuleSet::default(),
+            crate::DEFAULT_FUEL,
+            crate::metering::DEFAULT_ENGINE_MEMORY_PAGES,
+        )
+    }
+
+    /// Like [`PooledMatchClient::new`], but validates and meters every pooled runtime against
+    /// `rule_set`, `fuel`, and `engine_memory_pages` instead of the defaults (see
+    /// [`SnakeRuntime::with_limits`] for what `engine_memory_pages` bounds).
+    pub fn with_limits(
+        red: &[u8],
+        blue: &[u8],
+        pool_size: usize,
+        rule_set: &RuleSet,
+        fuel: u64,
+        engine_memory_pages: u32,
+    ) -> Result<Self, ValidationError> {
+        let pool = (0..pool_size.max(1))
+            .map(|_| {
+                SnakeRuntime::with_limits(red, blue, rule_set, fuel, engine_memory_pages)
+                    .map(|runtime| Arc::new(std::sync::Mutex::new(runtime)))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(PooledMatchClient { pool })
+    }
+
+    fn runtime_for(&self, seed: u32) -> Arc<std::sync::Mutex<SnakeRuntime>> {
+        let index = seed as usize % self.pool.len();
+        self.pool[index].clone()
+    }
+}
+
+impl SyncMatchClient for PooledMatchClient {
+    fn run_game(&mut self, seed: u32) -> GameResult {
+        let runtime = self.runtime_for(seed);
+        let mut runtime = runtime.lock().unwrap();
+        runtime.run_game(seed)
+    }
+}
+
+impl AsyncMatchClient for PooledMatchClient {
+    fn run_game(&self, seed: u32) -> Pin<Box<dyn Future<Output = GameResult> + Send + '_>> {
+        let runtime = self.runtime_for(seed);
+        Box::pin(async move {
+            tokio::task::spawn_blocking(move || {
+                let mut runtime = runtime.lock().unwrap();
+                runtime.run_game(seed)
+            })
+            .await
+            .expect("match worker thread panicked")
+        })
+    }
+
+    fn run_games(&self, seeds: Range<u32>) -> Pin<Box<dyn Stream<Item = GameResult> + Send + '_>> {
+        let concurrency = self.pool.len();
+        Box::pin(
+            stream::iter(seeds)
+                .map(move |seed| self.run_game(seed))
+                .buffer_unordered(concurrency),
+        )
+    }
+}
@@ -0,0 +1,217 @@
+//! C ABI for embedding `SnakeRuntime` into non-Rust hosts (C#, Python, Node, ...).
+//!
+//! Everything crosses the boundary as an opaque `Handle<T>` pointer, never a raw Rust
+//! reference. A handle tracks whether it has been freed: every accessor null-checks the
+//! pointer and checks that tombstone before touching the value, and `*_drop` drops the value
+//! and sets the tombstone but keeps the small handle allocation alive, so a call after
+//! `*_drop` (including a second `*_drop`) sees the tombstone instead of freed memory.
+//! `SnakeRuntime` is an
+//! *exclusive* handle (its methods take `&mut self` and the underlying wasmer `Store` is not
+//! `Sync`, so a handle must stay on the thread that created it); `GameResult` is a *shared*
+//! handle, since its accessors only ever read.
+//!
+//! Enabled by the `c_interface` feature.
+
+use std::slice;
+
+use crate::{GameResult, SnakeRuntime, Winner};
+
+/// A boxed value handed across the FFI boundary as a raw pointer, with a tombstone so a
+/// use-after-drop call degrades to a null/-1 return instead of undefined behavior.
+pub struct Handle<T> {
+    freed: bool,
+    value: Option<T>,
+}
+
+impl<T> Handle<T> {
+    fn new(value: T) -> *mut Handle<T> {
+        Box::into_raw(Box::new(Handle {
+            freed: false,
+            value: Some(value),
+        }))
+    }
+
+    /// Exclusive (mutable) access, for handles like `SnakeRuntime` whose methods mutate state.
+    unsafe fn get_mut<'a>(ptr: *mut Handle<T>) -> Option<&'a mut T> {
+        if ptr.is_null() {
+            return None;
+        }
+        let handle = &mut *ptr;
+        if handle.freed {
+            return None;
+        }
+        handle.value.as_mut()
+    }
+
+    /// Shared (read-only) access, for handles like `GameResult` whose accessors only read.
+    unsafe fn get<'a>(ptr: *mut Handle<T>) -> Option<&'a T> {
+        if ptr.is_null() {
+            return None;
+        }
+        let handle = &*ptr;
+        if handle.freed {
+            return None;
+        }
+        handle.value.as_ref()
+    }
+
+    /// Drops the inner value and marks the handle freed. The handle's own allocation is never
+    /// reclaimed, so the tombstone stays readable: calling `*_drop` twice is a no-op, and any
+    /// accessor called afterwards returns its null/-1 sentinel.
+    unsafe fn free(ptr: *mut Handle<T>) {
+        if ptr.is_null() {
+            return;
+        }
+        let handle = &mut *ptr;
+        if handle.freed {
+            return;
+        }
+        handle.freed = true;
+        handle.value = None;
+    }
+}
+
+/// Creates a `SnakeRuntime` from two in-memory WASM blobs. Returns null if either pointer is
+/// null. The returned handle must only ever be used from the thread that created it.
+#[no_mangle]
+pub extern "C" fn snake_runtime_new(
+    red_ptr: *const u8,
+    red_len: usize,
+    blue_ptr: *const u8,
+    blue_len: usize,
+) -> *mut Handle<SnakeRuntime> {
+    if red_ptr.is_null() || blue_ptr.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let red = unsafe { slice::from_raw_parts(red_ptr, red_len) };
+    let blue = unsafe { slice::from_raw_parts(blue_ptr, blue_len) };
+
+    match SnakeRuntime::new(red, blue) {
+        Ok(runtime) => Handle::new(runtime),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Runs one game for `seed`, returning a `GameResult` handle, or null if `handle` is invalid.
+#[no_mangle]
+pub extern "C" fn snake_runtime_run_game(
+    handle: *mut Handle<SnakeRuntime>,
+    seed: u32,
+) -> *mut Handle<GameResult> {
+    match unsafe { Handle::get_mut(handle) } {
+        Some(runtime) => Handle::new(runtime.run_game(seed)),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Frees a `SnakeRuntime` handle. Safe to call on null.
+#[no_mangle]
+pub extern "C" fn snake_runtime_drop(handle: *mut Handle<SnakeRuntime>) {
+    unsafe { Handle::free(handle) }
+}
+
+/// Returns 0 (RED), 1 (BLUE), 2 (TIE), or -1 if `handle` is invalid.
+#[no_mangle]
+pub extern "C" fn result_get_winner(handle: *mut Handle<GameResult>) -> i32 {
+    match unsafe { Handle::get(handle) } {
+        Some(result) => match result.winner {
+            Winner::Red => 0,
+            Winner::Blue => 1,
+            Winner::Tie => 2,
+        },
+        None => -1,
+    }
+}
+
+/// Returns the tick count the game ran for, or -1 if `handle` is invalid.
+#[no_mangle]
+pub extern "C" fn result_get_ticks(handle: *mut Handle<GameResult>) -> i32 {
+    unsafe { Handle::get(handle) }
+        .map(|result| result.tick as i32)
+        .unwrap_or(-1)
+}
+
+/// Returns the CPU cycle count the game ran for, or -1 if `handle` is invalid.
+#[no_mangle]
+pub extern "C" fn result_get_cycles(handle: *mut Handle<GameResult>) -> i32 {
+    unsafe { Handle::get(handle) }
+        .map(|result| result.cycle as i32)
+        .unwrap_or(-1)
+}
+
+/// Returns the byte length of the lose-reason string, or -1 if `handle` is invalid.
+#[no_mangle]
+pub extern "C" fn result_get_reason_len(handle: *mut Handle<GameResult>) -> i32 {
+    unsafe { Handle::get(handle) }
+        .map(|result| result.lose_reason.len() as i32)
+        .unwrap_or(-1)
+}
+
+/// Returns the byte at `index` in the lose-reason string, or -1 if `handle` or `index` is
+/// invalid.
+#[no_mangle]
+pub extern "C" fn result_get_reason_byte(handle: *mut Handle<GameResult>, index: i32) -> i32 {
+    match unsafe { Handle::get(handle) } {
+        Some(result) => result
+            .lose_reason
+            .as_bytes()
+            .get(index as usize)
+            .map(|byte| *byte as i32)
+            .unwrap_or(-1),
+        None => -1,
+    }
+}
+
+/// Frees a `GameResult` handle. Safe to call on null.
+#[no_mangle]
+pub extern "C" fn result_drop(handle: *mut Handle<GameResult>) {
+    unsafe { Handle::free(handle) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_result() -> GameResult {
+        GameResult {
+            seed: 7,
+            winner: Winner::Blue,
+            tick: 42,
+            cycle: 99,
+            lose_reason: "RED: starved".to_string(),
+        }
+    }
+
+    #[test]
+    fn result_accessors_read_through_a_live_handle() {
+        let handle = Handle::new(sample_result());
+
+        assert_eq!(result_get_winner(handle), 1);
+        assert_eq!(result_get_ticks(handle), 42);
+        assert_eq!(result_get_cycles(handle), 99);
+        assert_eq!(result_get_reason_len(handle), "RED: starved".len() as i32);
+        assert_eq!(result_get_reason_byte(handle, 0), b'R' as i32);
+        assert_eq!(result_get_reason_byte(handle, 999), -1);
+
+        result_drop(handle);
+    }
+
+    #[test]
+    fn result_accessors_return_the_invalid_sentinel_for_a_null_handle() {
+        let handle: *mut Handle<GameResult> = std::ptr::null_mut();
+
+        assert_eq!(result_get_winner(handle), -1);
+        assert_eq!(result_get_ticks(handle), -1);
+        assert_eq!(result_get_cycles(handle), -1);
+        assert_eq!(result_get_reason_len(handle), -1);
+        assert_eq!(result_get_reason_byte(handle, 0), -1);
+    }
+
+    #[test]
+    fn snake_runtime_new_returns_null_for_a_null_player_pointer() {
+        let blue = [0u8; 4];
+        let handle = snake_runtime_new(std::ptr::null(), 0, blue.as_ptr(), blue.len());
+        assert!(handle.is_null());
+    }
+}
@@ -1,10 +1,39 @@
-use serde::Serialize;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
 use wasmer::{imports, Function, Instance, Module, Store, Value};
 
+pub mod client;
+#[cfg(feature = "c_interface")]
+pub mod ffi;
+pub mod metering;
+pub mod output;
+pub mod replay;
+pub mod server;
+pub mod validation;
+
+use validation::{RuleSet, ValidationError};
+
 pub const SNAKE_RUNTIME_WASM: &[u8] = include_bytes!("snake_runtime.wasm");
 
+/// Default per-game instruction budget, used when a caller doesn't configure one explicitly.
+pub const DEFAULT_FUEL: u64 = 10_000_000;
+
+/// Per-winner tallies of why the losing snake died, each with a handful of example seeds.
+pub type LoseReasonTable = HashMap<Winner, HashMap<String, (u32, Vec<u32>)>>;
+
+/// Final win/tie/loss tallies for a batch of games, as emitted by `--json` and the server.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct JsonOutput {
+    pub red: u32,
+    pub tie: u32,
+    pub blue: u32,
+}
+
 pub struct SnakeRuntime {
     store: Store,
+    instance: Instance,
+    fuel: u64,
     red_ptr: i32,
     red_len: u32,
     blue_ptr: i32,
@@ -19,8 +48,65 @@ pub struct SnakeRuntime {
 }
 
 impl SnakeRuntime {
-    pub fn new(red_wasm: &[u8], blue_wasm: &[u8]) -> Self {
-        let mut store = Store::default();
+    /// Builds a runtime from two player WASM blobs, statically validating both against the
+    /// default `RuleSet` before anything is instantiated. Use [`SnakeRuntime::with_limits`] to
+    /// tune the validation, fuel, and memory limits (e.g. from CLI flags).
+    pub fn new(red_wasm: &[u8], blue_wasm: &[u8]) -> Result<Self, ValidationError> {
+        Self::with_limits(
+            red_wasm,
+            blue_wasm,
+            &RuleSet::default(),
+            DEFAULT_FUEL,
+            metering::DEFAULT_ENGINE_MEMORY_PAGES,
+        )
+    }
+
+    /// Like [`SnakeRuntime::new`], but validates both player modules against `rule_set` and
+    /// meters the match with `fuel` instructions and `engine_memory_pages` (64 KiB each) of
+    /// linear memory instead of the defaults. `engine_memory_pages` bounds the shared host
+    /// engine instance's own memory — which holds both players' raw bytes plus the engine's
+    /// game-state working set — not an individual player's declared memory; that's a separate,
+    /// much smaller check in `rule_set` (see `validation::MaxMemoryPages` and the `metering`
+    /// module docs). When the match exhausts either budget, `run_game` returns a forced tie
+    /// rather than letting the host spin or grow memory unbounded.
+    pub fn with_limits(
+        red_wasm: &[u8],
+        blue_wasm: &[u8],
+        rule_set: &RuleSet,
+        fuel: u64,
+        engine_memory_pages: u32,
+    ) -> Result<Self, ValidationError> {
+        let mut store = metering::metered_store(fuel, engine_memory_pages);
+
+        // Compile both sides before reporting anything: a RED compile failure shouldn't hide
+        // BLUE's own violations (or vice versa), since the whole point of `ValidationError` is
+        // to surface every violation from one submission, not to fail fast on the first one.
+        let mut violations = Vec::new();
+        let red_module = match Module::new(&store, red_wasm) {
+            Ok(module) => Some(module),
+            Err(e) => {
+                violations.push(Self::compile_violation("RED", &e));
+                None
+            }
+        };
+        let blue_module = match Module::new(&store, blue_wasm) {
+            Ok(module) => Some(module),
+            Err(e) => {
+                violations.push(Self::compile_violation("BLUE", &e));
+                None
+            }
+        };
+
+        if let Some(red_module) = &red_module {
+            violations.extend(rule_set.check("RED", red_wasm, red_module));
+        }
+        if let Some(blue_module) = &blue_module {
+            violations.extend(rule_set.check("BLUE", blue_wasm, blue_module));
+        }
+        if !violations.is_empty() {
+            return Err(ValidationError { violations });
+        }
+
         let module = Module::new(&store, &SNAKE_RUNTIME_WASM).unwrap();
         let import_object = imports! {};
         let instance = Instance::new(&mut store, &module, &import_object).unwrap();
@@ -43,8 +129,10 @@ impl SnakeRuntime {
         memory_view.write(red_ptr as u64, red_wasm).unwrap();
         memory_view.write(blue_ptr as u64, blue_wasm).unwrap();
 
-        SnakeRuntime {
+        Ok(SnakeRuntime {
             store,
+            instance: instance.clone(),
+            fuel,
             red_ptr,
             red_len: red_wasm.len() as u32,
             blue_ptr,
@@ -80,26 +168,59 @@ impl SnakeRuntime {
                 .get_function("result_drop")
                 .unwrap()
                 .clone(),
+        })
+    }
+
+    fn compile_violation(team: &str, error: &wasmer::CompileError) -> validation::Violation {
+        validation::Violation {
+            team: team.to_string(),
+            rule: "compiles".to_string(),
+            reason: format!("failed to compile as WASM: {error}"),
         }
     }
 }
 
 impl SnakeRuntime {
     pub fn run_game(&mut self, seed: u32) -> GameResult {
-        let result_ptr = self
-            .run_game
-            .call(
-                &mut self.store,
-                &[
-                    Value::I32(self.red_ptr),
-                    Value::I32(self.red_len as i32),
-                    Value::I32(self.blue_ptr),
-                    Value::I32(self.blue_len as i32),
-                    Value::I32(seed as i32),
-                ],
-            )
-            .unwrap()[0]
-            .unwrap_i32();
+        metering::reset_fuel(&mut self.store, &self.instance, self.fuel);
+
+        let call_result = self.run_game.call(
+            &mut self.store,
+            &[
+                Value::I32(self.red_ptr),
+                Value::I32(self.red_len as i32),
+                Value::I32(self.blue_ptr),
+                Value::I32(self.blue_len as i32),
+                Value::I32(seed as i32),
+            ],
+        );
+
+        let result_ptr = match call_result {
+            Ok(values) => values[0].unwrap_i32(),
+            Err(trap) => {
+                // Known deviation: the request behind this metering asked for the trapping
+                // side to take a deterministic *loss* (e.g. "RED: fuel exhausted"), not a tie.
+                // That requires knowing which side was mid-turn when the fuel/memory budget
+                // ran out. The engine call above interprets both players' turns from inside
+                // one shared instance, and the trap it raises carries no per-side attribution
+                // the host can read back out — so a compliant player and a misbehaving one are
+                // indistinguishable from here. Forcing a tie is the honest fallback until the
+                // engine itself exports which side it was executing when metering tripped; see
+                // the `metering` module docs.
+                let reason = if metering::is_fuel_exhausted(&mut self.store, &self.instance) {
+                    "BOTH: fuel exhausted".to_string()
+                } else {
+                    format!("BOTH: resource limit exceeded ({trap})")
+                };
+                return GameResult {
+                    seed,
+                    winner: Winner::Tie,
+                    tick: 0,
+                    cycle: 0,
+                    lose_reason: reason,
+                };
+            }
+        };
 
         let reason_len = self
             .result_get_reason_len
@@ -127,16 +248,17 @@ impl SnakeRuntime {
             .i32()
             .unwrap();
 
-        let winner = match winner_value {
-            0 => Winner::Red,
-            1 => Winner::Blue,
-            2 => Winner::Tie,
-            3 => {
-                panic!("RED WASM failed validation");
-            }
-            4 => {
-                panic!("BLUE WASM failed validation");
-            }
+        // 3/4 are the engine's own runtime check that a player violated the match protocol
+        // (e.g. returned a malformed move) — a check the host-side `RuleSet` screens for
+        // statically but can't prove unreachable, since it never runs the player. Surface it
+        // as a `GameResult` with the offending team losing, not a panic that takes down the
+        // worker thread.
+        let (winner, lose_reason) = match winner_value {
+            0 => (Winner::Red, reason.to_string()),
+            1 => (Winner::Blue, reason.to_string()),
+            2 => (Winner::Tie, reason.to_string()),
+            3 => (Winner::Blue, format!("RED: failed runtime validation ({reason})")),
+            4 => (Winner::Red, format!("BLUE: failed runtime validation ({reason})")),
             _ => unreachable!(),
         };
 
@@ -158,16 +280,27 @@ impl SnakeRuntime {
             .unwrap();
 
         GameResult {
+            seed,
             winner,
             tick: ticks as u32,
             cycle: cycles as u32,
-            lose_reason: reason.to_string(),
+            lose_reason,
         }
     }
+
+    /// Decodes a replay file written by the `batch` CLI subcommand's `--replay-dir` (see
+    /// `replay::write_replay`), yielding its per-tick frames (always empty today — see the
+    /// `replay` module docs).
+    pub fn replay_file(
+        path: &std::path::Path,
+    ) -> std::io::Result<impl Iterator<Item = replay::BoardSnapshot>> {
+        Ok(replay::read_frames(path)?.into_iter())
+    }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct GameResult {
+    pub seed: u32,
     pub winner: Winner,
     pub tick: u32,
     pub cycle: u32,
@@ -1,20 +1,34 @@
-use std::{
-    collections::HashMap,
-    fs::File,
-    io::Read,
-    num::NonZeroUsize,
-    sync::{Arc, Mutex},
-};
+use std::{collections::HashMap, fs::File, io::Read, num::NonZeroUsize, path::Path};
 
 use clap::Parser;
 use cli_table::{print_stdout, Cell, Table};
 use colored::Colorize;
-use serde::{Deserialize, Serialize};
-use snake_runtime::{SnakeRuntime, Winner};
+use futures::StreamExt;
+use snake_runtime::{
+    client::{AsyncMatchClient, PooledMatchClient},
+    validation::RuleSet,
+    JsonOutput, SnakeRuntime, Winner,
+};
 
 #[derive(Parser, Debug)]
 #[command(author, about, long_about = None)]
-struct Args {
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Commands {
+    /// Run a fixed batch of games between two players and report the results
+    Batch(BatchArgs),
+    /// Start an HTTP match server that accepts players and matches over the network
+    Server(ServerArgs),
+    /// Re-derive a game's result from a replay file and check it against a live run
+    Replay(ReplayArgs),
+}
+
+#[derive(Parser, Debug)]
+struct BatchArgs {
     /// WASM file for RED (team 0) player
     #[arg(short, long)]
     red: String,
@@ -38,32 +52,142 @@ struct Args {
     /// Print JSON statistics to stdout instead of the default human readable output
     #[arg(long)]
     json: bool,
+
+    /// Maximum linear-memory pages (64 KiB each) a player module may declare, checked statically
+    /// against the module alone — not a runtime cap (see --engine-memory-pages for that)
+    #[arg(long, default_value = "16")]
+    max_memory_pages: u32,
+
+    /// Maximum player module size, in bytes
+    #[arg(long, default_value = "1048576")]
+    max_code_size: usize,
+
+    /// Linear-memory pages (64 KiB each) available to the shared host engine instance at
+    /// runtime, which holds both players' raw bytes plus the engine's own game-state working
+    /// set. Unrelated to --max-memory-pages; raise this if --max-code-size is raised well past
+    /// its default
+    #[arg(long, default_value_t = snake_runtime::metering::DEFAULT_ENGINE_MEMORY_PAGES)]
+    engine_memory_pages: u32,
+
+    /// Export a player module must provide to be accepted (may be passed multiple times)
+    #[arg(long = "required-export")]
+    required_exports: Vec<String>,
+
+    /// Instruction budget per game. A player that exhausts it forces a tie instead of spinning
+    /// the host forever.
+    #[arg(long, default_value = "10000000")]
+    fuel: u64,
+
+    /// Directory to write one bit-packed replay file per seed into
+    #[arg(long)]
+    replay_dir: Option<String>,
+
+    /// File to stream one record per finished game into, as the batch progresses
+    #[arg(long)]
+    output: Option<String>,
+
+    /// Record format for --output
+    #[arg(long, value_enum, default_value = "jsonl")]
+    format: OutputFormatArg,
+}
+
+/// CLI-facing mirror of `snake_runtime::output::OutputFormat`, kept separate so the library
+/// doesn't need to depend on clap.
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum OutputFormatArg {
+    Jsonl,
+    Csv,
+}
+
+impl From<OutputFormatArg> for snake_runtime::output::OutputFormat {
+    fn from(value: OutputFormatArg) -> Self {
+        match value {
+            OutputFormatArg::Jsonl => snake_runtime::output::OutputFormat::Jsonl,
+            OutputFormatArg::Csv => snake_runtime::output::OutputFormat::Csv,
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+struct ServerArgs {
+    /// Address to bind the HTTP match server to
+    #[arg(short, long, default_value = "127.0.0.1:8080")]
+    listen: String,
+}
+
+#[derive(Parser, Debug)]
+struct ReplayArgs {
+    /// WASM file for RED (team 0) player
+    #[arg(short, long)]
+    red: String,
+
+    /// WASM file for BLUE (team 1) player
+    #[arg(short, long)]
+    blue: String,
+
+    /// Replay file written by `batch --replay-dir`
+    file: String,
 }
 
 struct State {
-    seed: u32,
     wins: HashMap<Winner, u32>,
     lose_reasons: HashMap<Winner, HashMap<String, (u32, Vec<u32>)>>,
 }
 
-#[derive(Serialize, Deserialize)]
-struct JsonOutput {
-    red: u32,
-    tie: u32,
-    blue: u32,
+pub fn main() {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Commands::Batch(args) => run_batch(args),
+        Commands::Server(args) => run_server(args),
+        Commands::Replay(args) => run_replay(args),
+    }
 }
 
-pub fn main() {
-    let args = Args::parse();
+fn run_replay(args: ReplayArgs) {
+    let (header, recorded) = snake_runtime::replay::read_header_and_result(Path::new(&args.file))
+        .expect("failed to read replay file");
 
     let red = get_wasm_file_bytes(&args.red);
     let blue = get_wasm_file_bytes(&args.blue);
 
-    let seed_mutex = Arc::new(Mutex::new(State {
-        seed: args.seed,
-        wins: HashMap::new(),
-        lose_reasons: HashMap::new(),
-    }));
+    let mut runtime = SnakeRuntime::new(&red, &blue).unwrap_or_else(|violations| {
+        eprintln!("{violations}");
+        std::process::exit(1);
+    });
+    let live = runtime.run_game(header.seed);
+
+    if live.winner == recorded.winner && live.tick == recorded.tick && live.cycle == recorded.cycle
+    {
+        println!(
+            "Replay for seed {:05} is deterministic: {:?} in {} ticks ({} cycles)",
+            header.seed, live.winner, live.tick, live.cycle
+        );
+    } else {
+        eprintln!(
+            "Replay mismatch for seed {:05}: recorded {:?} ({}:{}) vs live {:?} ({}:{})",
+            header.seed,
+            recorded.winner,
+            recorded.tick,
+            recorded.cycle,
+            live.winner,
+            live.tick,
+            live.cycle
+        );
+        std::process::exit(1);
+    }
+}
+
+fn run_server(args: ServerArgs) {
+    let addr: std::net::SocketAddr = args.listen.parse().expect("invalid --listen address");
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(snake_runtime::server::serve(addr));
+}
+
+fn run_batch(args: BatchArgs) {
+    let red = get_wasm_file_bytes(&args.red);
+    let blue = get_wasm_file_bytes(&args.blue);
 
     let num_threads = args.threads.unwrap_or_else(|| {
         std::thread::available_parallelism()
@@ -71,74 +195,128 @@ pub fn main() {
             .unwrap_or(1)
     });
 
-    if u32::checked_add(args.games, args.seed).is_none() {
-        println!("Seed is too high for the number of games selected!");
-    }
+    // Checked up front so a seed range that overflows a u32 is rejected with a clear reason
+    // instead of silently wrapping into a shorter (or looping) seed range further down.
+    let end_seed = args.seed.checked_add(args.games).unwrap_or_else(|| {
+        eprintln!(
+            "seed {} + games {} overflows a u32; lower one of them",
+            args.seed, args.games
+        );
+        std::process::exit(1);
+    });
 
     if !args.json {
         println!("Running {} games with {} threads", args.games, num_threads);
     }
 
-    let mut threads = vec![];
-
-    for _ in 0..num_threads {
-        let red = red.clone();
-        let blue = blue.clone();
-        let seed_mutex = seed_mutex.clone();
-
-        threads.push(std::thread::spawn(move || {
-            let mut runtime = SnakeRuntime::new(&red, &blue);
-
-            loop {
-                let seed = {
-                    let mut state = seed_mutex.lock().unwrap();
-                    let seed = state.seed;
-                    if seed + 1 > (args.games + args.seed) {
-                        return;
+    let rule_set = RuleSet::standard(
+        args.max_memory_pages,
+        args.max_code_size,
+        args.required_exports.clone(),
+    );
+    let client = match PooledMatchClient::with_limits(
+        &red,
+        &blue,
+        num_threads,
+        &rule_set,
+        args.fuel,
+        args.engine_memory_pages,
+    ) {
+        Ok(client) => client,
+        Err(violations) => {
+            eprintln!("{violations}");
+            std::process::exit(1);
+        }
+    };
+    let json = args.json;
+    let replay_dir = args.replay_dir.clone();
+    let result_writer = args
+        .output
+        .as_ref()
+        .map(|path| {
+            snake_runtime::output::ResultWriter::spawn(path, args.format.into())
+                .unwrap_or_else(|e| {
+                    eprintln!("failed to open --output file {path}: {e}");
+                    std::process::exit(1);
+                })
+        });
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let state = rt.block_on(async {
+        client
+            .run_games(args.seed..end_seed)
+            .fold(
+                State {
+                    wins: HashMap::new(),
+                    lose_reasons: HashMap::new(),
+                },
+                |mut state, result| {
+                    let replay_dir = replay_dir.clone();
+                    let result_writer = result_writer.as_ref();
+                    async move {
+                        if let Some(writer) = result_writer {
+                            writer.send(result.clone());
+                        }
+
+                        *state.wins.entry(result.winner).or_insert(0) += 1;
+
+                        let examples = state
+                            .lose_reasons
+                            .entry(result.winner)
+                            .or_default()
+                            .entry(result.lose_reason.clone())
+                            .or_default();
+
+                        if examples.1.len() < 5 {
+                            examples.1.push(result.seed);
+                        }
+                        examples.0 += 1;
+
+                        if let Some(dir) = &replay_dir {
+                            let path = Path::new(dir).join(format!("{:08}.replay", result.seed));
+                            let header = snake_runtime::replay::ReplayHeader {
+                                width: 0,
+                                height: 0,
+                                seed: result.seed,
+                                tick_count: result.tick,
+                            };
+                            if let Err(e) =
+                                snake_runtime::replay::write_replay(&path, header, &result)
+                            {
+                                eprintln!("failed to write replay for seed {}: {e}", result.seed);
+                            }
+                        }
+
+                        let winner_str = match result.winner {
+                            Winner::Red => "RED".red(),
+                            Winner::Blue => "BLUE".blue(),
+                            Winner::Tie => "TIE".white(),
+                        };
+                        if !json {
+                            println!(
+                                "{:05} = {} ({}:{:05}) {}",
+                                result.seed,
+                                winner_str,
+                                result.tick,
+                                result.cycle,
+                                result.lose_reason
+                            );
+                        }
+
+                        state
                     }
-                    state.seed += 1;
-                    seed
-                };
-                let result = runtime.run_game(seed);
-
-                {
-                    let mut state = seed_mutex.lock().unwrap();
-                    *state.wins.entry(result.winner).or_insert(0) += 1;
-
-                    let examples = state
-                        .lose_reasons
-                        .entry(result.winner)
-                        .or_default()
-                        .entry(result.lose_reason.clone())
-                        .or_default();
-
-                    if examples.1.len() < 5 {
-                        examples.1.push(seed);
-                    }
-                    examples.0 += 1;
-                }
-
-                let winner_str = match result.winner {
-                    Winner::Red => "RED".red(),
-                    Winner::Blue => "BLUE".blue(),
-                    Winner::Tie => "TIE".white(),
-                };
-                if !args.json {
-                    println!(
-                        "{:05} = {} ({}:{:05}) {}",
-                        seed, winner_str, result.tick, result.cycle, result.lose_reason
-                    );
-                }
-            }
-        }));
-    }
+                },
+            )
+            .await
+    });
 
-    for thread_handle in threads {
-        thread_handle.join().unwrap();
+    if let Some(writer) = result_writer {
+        writer.join().unwrap_or_else(|e| {
+            eprintln!("failed to flush --output file: {e}");
+            std::process::exit(1);
+        });
     }
 
-    let state = seed_mutex.lock().unwrap();
-
     let red_wins = state.wins.get(&Winner::Red).cloned().unwrap_or(0);
     let ties = state.wins.get(&Winner::Tie).cloned().unwrap_or(0);
     let blue_wins = state.wins.get(&Winner::Blue).cloned().unwrap_or(0);
@@ -0,0 +1,167 @@
+//! Resource metering: bounds how much compute (`fuel`) and linear memory a match may use, so a
+//! pathological player WASM can't spin the host forever or grow memory without limit.
+//!
+//! The engine instantiated from `SNAKE_RUNTIME_WASM` is the only WASM execution context the
+//! host controls — it interprets both players' raw bytes internally, so a metering trap can't
+//! be attributed to a specific side from here. `SnakeRuntime::run_game` surfaces it as a forced
+//! tie instead of guessing which team was at fault.
+//!
+//! Known deviation: per-team attribution (a structured "RED: fuel exhausted" loss for whichever
+//! side actually blew the budget) was the original ask for this metering. It isn't implemented
+//! because it isn't possible from the host side alone — `SNAKE_RUNTIME_WASM` is a prebuilt blob
+//! with no source in this repo, and its current exports don't say which side it was executing
+//! when the trap fired. Attributing the fault would need a new export on that engine; until then
+//! a forced tie is the honest behavior.
+//!
+//! `metered_store`'s memory cap is *not* the same thing as `validation::MaxMemoryPages`. Player
+//! modules are never instantiated on their own — their raw bytes are just copied into the one
+//! `SNAKE_RUNTIME_WASM` instance's linear memory (see `SnakeRuntime::with_limits` in `lib.rs`),
+//! which also holds that engine's own game-state working set. So the page count passed here
+//! bounds the *whole shared engine instance*, not an individual player's memory, and it has to
+//! stay comfortably above twice `validation::DEFAULT_MAX_CODE_SIZE` (room for both players' raw
+//! bytes) plus headroom for the engine's own state — see [`DEFAULT_ENGINE_MEMORY_PAGES`].
+
+use std::{ptr::NonNull, sync::Arc};
+
+use wasmer::{
+    vm::{VMMemory, VMMemoryDefinition, VMTable, VMTableDefinition},
+    BaseTunables, CompilerConfig, Engine, Instance, MemoryError, MemoryStyle, MemoryType, Pages,
+    Store, TableStyle, TableType, Tunables,
+};
+use wasmer_compiler_cranelift::Cranelift;
+use wasmer_middlewares::{
+    metering::{get_remaining_points, set_remaining_points, MeteringPoints},
+    Metering,
+};
+
+/// Default cap, in pages (64 KiB each), on the shared host engine instance's own linear memory
+/// — see the module docs. Sized to comfortably fit both players' raw bytes at
+/// `validation::DEFAULT_MAX_CODE_SIZE` each, plus headroom for the engine's own game-state
+/// working set; raise it if `--max-code-size`/`max_code_size` is raised well past its default.
+pub const DEFAULT_ENGINE_MEMORY_PAGES: u32 = 512; // 32 MiB
+
+/// Per-instruction fuel cost. Every instruction costs one unit, so `fuel` is roughly an
+/// instruction budget for a single game.
+fn cost_function(_operator: &wasmer::wasmparser::Operator) -> u64 {
+    1
+}
+
+/// Wraps `BaseTunables`, clamping every memory's maximum to `max_pages`.
+struct CappedTunables {
+    base: BaseTunables,
+    max_pages: Pages,
+}
+
+impl CappedTunables {
+    fn capped(&self, memory: &MemoryType) -> MemoryType {
+        let mut memory = *memory;
+        memory.maximum = Some(
+            memory
+                .maximum
+                .map_or(self.max_pages, |requested| requested.min(self.max_pages)),
+        );
+        memory
+    }
+}
+
+impl Tunables for CappedTunables {
+    fn memory_style(&self, memory: &MemoryType) -> MemoryStyle {
+        self.base.memory_style(&self.capped(memory))
+    }
+
+    fn table_style(&self, table: &TableType) -> TableStyle {
+        self.base.table_style(table)
+    }
+
+    fn create_host_memory(
+        &self,
+        ty: &MemoryType,
+        style: &MemoryStyle,
+    ) -> Result<VMMemory, MemoryError> {
+        self.base.create_host_memory(&self.capped(ty), style)
+    }
+
+    unsafe fn create_vm_memory(
+        &self,
+        ty: &MemoryType,
+        style: &MemoryStyle,
+        vm_definition_location: NonNull<VMMemoryDefinition>,
+    ) -> Result<VMMemory, MemoryError> {
+        self.base
+            .create_vm_memory(&self.capped(ty), style, vm_definition_location)
+    }
+
+    fn create_host_table(&self, ty: &TableType, style: &TableStyle) -> Result<VMTable, String> {
+        self.base.create_host_table(ty, style)
+    }
+
+    unsafe fn create_vm_table(
+        &self,
+        ty: &TableType,
+        style: &TableStyle,
+        vm_definition_location: NonNull<VMTableDefinition>,
+    ) -> Result<VMTable, String> {
+        self.base.create_vm_table(ty, style, vm_definition_location)
+    }
+}
+
+/// Builds a `Store` metered with a `fuel`-instruction budget per game, whose host engine
+/// instance is capped at `engine_memory_pages` (64 KiB each) of linear memory — see the module
+/// docs for why that's the whole shared engine, not one player's memory.
+pub fn metered_store(fuel: u64, engine_memory_pages: u32) -> Store {
+    let mut compiler_config = Cranelift::default();
+    compiler_config.push_middleware(Arc::new(Metering::new(fuel, cost_function)));
+
+    let engine: Engine = compiler_config.into();
+    let tunables = CappedTunables {
+        base: BaseTunables::for_target(engine.target()),
+        max_pages: Pages(engine_memory_pages),
+    };
+
+    Store::new_with_tunables(engine, tunables)
+}
+
+/// Resets `instance`'s remaining fuel to `fuel`. Call this before each game, since a
+/// `SnakeRuntime` reuses the same instance across many calls to `run_game`.
+pub fn reset_fuel(store: &mut Store, instance: &Instance, fuel: u64) {
+    set_remaining_points(store, instance, fuel);
+}
+
+/// Returns `true` once `instance` has exhausted its fuel budget.
+pub fn is_fuel_exhausted(store: &mut Store, instance: &Instance) -> bool {
+    matches!(
+        get_remaining_points(store, instance),
+        MeteringPoints::Exhausted
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasmer::{imports, Module};
+
+    #[test]
+    fn cost_function_charges_one_unit_per_instruction() {
+        assert_eq!(cost_function(&wasmer::wasmparser::Operator::Nop), 1);
+    }
+
+    #[test]
+    fn metered_store_caps_an_unbounded_memory_at_engine_memory_pages() {
+        let mut store = metered_store(1_000, 4);
+        let module = Module::new(&store, r#"(module (memory (export "memory") 1))"#).unwrap();
+        let instance = Instance::new(&mut store, &module, &imports! {}).unwrap();
+
+        let memory = instance.exports.get_memory("memory").unwrap();
+        assert_eq!(memory.ty(&store).maximum, Some(Pages(4)));
+    }
+
+    #[test]
+    fn reset_fuel_then_is_fuel_exhausted_is_false_for_a_fresh_budget() {
+        let mut store = metered_store(10, 4);
+        let module = Module::new(&store, r#"(module (func (export "f")))"#).unwrap();
+        let instance = Instance::new(&mut store, &module, &imports! {}).unwrap();
+
+        reset_fuel(&mut store, &instance, 10);
+        assert!(!is_fuel_exhausted(&mut store, &instance));
+    }
+}
@@ -0,0 +1,185 @@
+//! Streaming structured output: one record per finished game, written to a file as the batch
+//! progresses instead of buffered until the end.
+//!
+//! [`ResultWriter`] owns the output file and a dedicated writer thread. Finished `GameResult`s
+//! are pushed onto a `crossbeam_channel` instead of being written inline by whichever caller
+//! finished a game, so a slow disk never blocks the match loop and there's no lock shared
+//! between producers — each sender just pushes and moves on.
+
+use std::{
+    fs::File,
+    io::{self, Write},
+    path::Path,
+    thread::JoinHandle,
+};
+
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use serde::Serialize;
+
+use crate::{GameResult, Winner};
+
+/// Output record format for [`ResultWriter`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Jsonl,
+    Csv,
+}
+
+#[derive(Serialize)]
+struct OutputRecord<'a> {
+    seed: u32,
+    winner: Winner,
+    tick: u32,
+    cycle: u32,
+    lose_reason: &'a str,
+}
+
+/// Streams finished `GameResult`s to a file on a dedicated writer thread.
+pub struct ResultWriter {
+    sender: Sender<GameResult>,
+    handle: JoinHandle<io::Result<()>>,
+}
+
+impl ResultWriter {
+    /// Opens `path` and starts the writer thread.
+    pub fn spawn(path: impl AsRef<Path>, format: OutputFormat) -> io::Result<Self> {
+        let file = File::create(path)?;
+        let (sender, receiver) = unbounded();
+        let handle = std::thread::spawn(move || Self::run(file, format, receiver));
+
+        Ok(ResultWriter { sender, handle })
+    }
+
+    /// Queues `result` to be written. Never blocks on file I/O.
+    pub fn send(&self, result: GameResult) {
+        // Sending fails only if the writer thread has already stopped on an I/O error; `join`
+        // reports that error, so the failed send is ignored here.
+        let _ = self.sender.send(result);
+    }
+
+    /// Disconnects the channel and waits for every queued record to be flushed to disk.
+    pub fn join(self) -> io::Result<()> {
+        drop(self.sender);
+        self.handle.join().expect("output writer thread panicked")
+    }
+
+    fn run(mut file: File, format: OutputFormat, receiver: Receiver<GameResult>) -> io::Result<()> {
+        if format == OutputFormat::Csv {
+            writeln!(file, "seed,winner,tick,cycle,lose_reason")?;
+        }
+
+        for result in receiver {
+            match format {
+                OutputFormat::Jsonl => {
+                    let record = OutputRecord {
+                        seed: result.seed,
+                        winner: result.winner,
+                        tick: result.tick,
+                        cycle: result.cycle,
+                        lose_reason: &result.lose_reason,
+                    };
+                    writeln!(
+                        file,
+                        "{}",
+                        serde_json::to_string(&record)
+                            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+                    )?;
+                }
+                OutputFormat::Csv => {
+                    writeln!(
+                        file,
+                        "{},{:?},{},{},{}",
+                        result.seed,
+                        result.winner,
+                        result.tick,
+                        result.cycle,
+                        csv_escape(&result.lose_reason)
+                    )?;
+                }
+            }
+        }
+
+        file.flush()
+    }
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_escape_leaves_a_plain_field_untouched() {
+        assert_eq!(csv_escape("RED: starved"), "RED: starved");
+    }
+
+    #[test]
+    fn csv_escape_quotes_a_field_containing_a_comma() {
+        assert_eq!(csv_escape("RED: hit BLUE, died"), "\"RED: hit BLUE, died\"");
+    }
+
+    #[test]
+    fn csv_escape_doubles_embedded_quotes_and_wraps_the_field() {
+        assert_eq!(csv_escape("said \"hi\""), "\"said \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn csv_escape_quotes_a_field_containing_a_newline() {
+        assert_eq!(csv_escape("line one\nline two"), "\"line one\nline two\"");
+    }
+
+    #[test]
+    fn result_writer_writes_one_jsonl_record_per_result() {
+        let path = std::env::temp_dir().join("snake_runtime_output_jsonl_test.jsonl");
+        let writer = ResultWriter::spawn(&path, OutputFormat::Jsonl).unwrap();
+        writer.send(GameResult {
+            seed: 1,
+            winner: Winner::Red,
+            tick: 10,
+            cycle: 5,
+            lose_reason: "BLUE: starved".to_string(),
+        });
+        writer.send(GameResult {
+            seed: 2,
+            winner: Winner::Tie,
+            tick: 20,
+            cycle: 7,
+            lose_reason: "BOTH: fuel exhausted".to_string(),
+        });
+        writer.join().unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("\"seed\":1"));
+        assert!(lines[1].contains("\"seed\":2"));
+    }
+
+    #[test]
+    fn result_writer_writes_a_csv_header_then_one_row_per_result() {
+        let path = std::env::temp_dir().join("snake_runtime_output_csv_test.csv");
+        let writer = ResultWriter::spawn(&path, OutputFormat::Csv).unwrap();
+        writer.send(GameResult {
+            seed: 1,
+            winner: Winner::Blue,
+            tick: 10,
+            cycle: 5,
+            lose_reason: "RED: hit BLUE, died".to_string(),
+        });
+        writer.join().unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines[0], "seed,winner,tick,cycle,lose_reason");
+        assert_eq!(lines[1], "1,Blue,10,5,\"RED: hit BLUE, died\"");
+    }
+}
@@ -0,0 +1,273 @@
+//! Bit-packed replay capture and playback.
+//!
+//! `BitPackedWriter` accumulates sub-byte-width fields into a dense, big-endian-packed byte
+//! stream, flushing a full byte to `data` every 8 bits; `byte_align` zero-pads to the next byte
+//! boundary so a following section can start byte-aligned. `BitPackedReader` reverses both
+//! operations for playback.
+//!
+//! A replay file is a byte-aligned header (grid dimensions, seed, tick count), followed by a
+//! byte-aligned match summary (winner, tick, cycle, lose reason), followed by per-tick frames.
+//!
+//! Known scope conflict, flagged back rather than landed silently: the request behind this
+//! module asked for real per-tick capture — 2-bit-packed snake-head moves, bit-packed food
+//! coordinates, and deaths, replayable frame by frame. That needs new exports on
+//! `SNAKE_RUNTIME_WASM` (to read board state frame by frame, adjacent to the existing `result_*`
+//! accessors) that this prebuilt engine binary does not provide, and there's no engine source in
+//! this repo to add them to. Until those ship, replay files carry the header and summary only —
+//! `width`/`height` in [`ReplayHeader`] are always written as `0` (the engine doesn't expose
+//! board dimensions either), [`BoardSnapshot`] only carries a `tick`, [`read_frames`] always
+//! returns an empty frame list, and [`SnakeRuntime::replay_file`] in `lib.rs` yields no
+//! `BoardSnapshot`s. What does work today: the on-disk bit-packed format itself, and the
+//! `replay` CLI subcommand's determinism check (recorded summary vs. a live re-run of the same
+//! seed).
+
+use std::{
+    fs::File,
+    io::{self, Read, Write},
+    path::Path,
+};
+
+use crate::{GameResult, Winner};
+
+/// Accumulates sub-byte-width fields into a dense, big-endian-packed byte stream.
+#[derive(Default)]
+pub struct BitPackedWriter {
+    data: Vec<u8>,
+    cur: u8,
+    nextbits: usize,
+}
+
+impl BitPackedWriter {
+    pub fn new() -> Self {
+        BitPackedWriter::default()
+    }
+
+    /// Packs the low `n` bits of `value` into the stream, most-significant-bit first,
+    /// flushing a full byte to `data` whenever 8 bits have accumulated.
+    pub fn write_bits(&mut self, value: u32, n: usize) {
+        for i in (0..n).rev() {
+            let bit = ((value >> i) & 1) as u8;
+            self.cur = (self.cur << 1) | bit;
+            self.nextbits += 1;
+            if self.nextbits == 8 {
+                self.data.push(self.cur);
+                self.cur = 0;
+                self.nextbits = 0;
+            }
+        }
+    }
+
+    /// Zero-pads the current byte so a following `write_bits` call starts byte-aligned.
+    pub fn byte_align(&mut self) {
+        if self.nextbits > 0 {
+            self.cur <<= 8 - self.nextbits;
+            self.data.push(self.cur);
+            self.cur = 0;
+            self.nextbits = 0;
+        }
+    }
+
+    /// Consumes the writer, byte-aligning first if needed.
+    pub fn into_bytes(mut self) -> Vec<u8> {
+        self.byte_align();
+        self.data
+    }
+}
+
+/// Reverses [`BitPackedWriter`]: reads sub-byte-width fields back out of a packed byte stream.
+pub struct BitPackedReader<'a> {
+    data: &'a [u8],
+    byte_pos: usize,
+    bit_pos: usize,
+}
+
+impl<'a> BitPackedReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        BitPackedReader {
+            data,
+            byte_pos: 0,
+            bit_pos: 0,
+        }
+    }
+
+    /// Reads `n` bits (most-significant-bit first), or `None` once past the end of the buffer.
+    pub fn read_bits(&mut self, n: usize) -> Option<u32> {
+        let mut value = 0u32;
+        for _ in 0..n {
+            let byte = *self.data.get(self.byte_pos)?;
+            let bit = (byte >> (7 - self.bit_pos)) & 1;
+            value = (value << 1) | bit as u32;
+            self.bit_pos += 1;
+            if self.bit_pos == 8 {
+                self.bit_pos = 0;
+                self.byte_pos += 1;
+            }
+        }
+        Some(value)
+    }
+
+    /// Skips any partial byte so a following `read_bits` call starts byte-aligned.
+    pub fn byte_align(&mut self) {
+        if self.bit_pos > 0 {
+            self.bit_pos = 0;
+            self.byte_pos += 1;
+        }
+    }
+}
+
+/// A replay file's byte-aligned header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayHeader {
+    pub width: u16,
+    pub height: u16,
+    pub seed: u32,
+    pub tick_count: u32,
+}
+
+/// One decoded frame of a replay. Carries only the frame's `tick`: no snake moves, food
+/// changes or deaths are recorded or decoded.
+#[derive(Debug, Clone)]
+pub struct BoardSnapshot {
+    pub tick: u32,
+}
+
+/// Writes a replay file: `header`, then `result` (the match summary), then per-tick frames
+/// (currently always none — see module docs).
+pub fn write_replay(path: &Path, header: ReplayHeader, result: &GameResult) -> io::Result<()> {
+    let mut writer = BitPackedWriter::new();
+
+    writer.write_bits(header.width as u32, 16);
+    writer.write_bits(header.height as u32, 16);
+    writer.write_bits(header.seed, 32);
+    writer.write_bits(header.tick_count, 32);
+    writer.byte_align();
+
+    let winner_code = match result.winner {
+        Winner::Red => 0,
+        Winner::Blue => 1,
+        Winner::Tie => 2,
+    };
+    writer.write_bits(winner_code, 2);
+    writer.byte_align();
+    writer.write_bits(result.tick, 32);
+    writer.write_bits(result.cycle, 32);
+
+    let reason_bytes = result.lose_reason.as_bytes();
+    writer.write_bits(reason_bytes.len() as u32, 32);
+    for &byte in reason_bytes {
+        writer.write_bits(byte as u32, 8);
+    }
+    writer.byte_align();
+
+    File::create(path)?.write_all(&writer.into_bytes())
+}
+
+/// Reads a replay file's header and match summary back out.
+pub fn read_header_and_result(path: &Path) -> io::Result<(ReplayHeader, GameResult)> {
+    let mut bytes = vec![];
+    File::open(path)?.read_to_end(&mut bytes)?;
+    let mut reader = BitPackedReader::new(&bytes);
+
+    let missing = || io::Error::new(io::ErrorKind::UnexpectedEof, "truncated replay file");
+
+    let header = ReplayHeader {
+        width: reader.read_bits(16).ok_or_else(missing)? as u16,
+        height: reader.read_bits(16).ok_or_else(missing)? as u16,
+        seed: reader.read_bits(32).ok_or_else(missing)?,
+        tick_count: reader.read_bits(32).ok_or_else(missing)?,
+    };
+    reader.byte_align();
+
+    let winner = match reader.read_bits(2).ok_or_else(missing)? {
+        0 => Winner::Red,
+        1 => Winner::Blue,
+        _ => Winner::Tie,
+    };
+    reader.byte_align();
+    let tick = reader.read_bits(32).ok_or_else(missing)?;
+    let cycle = reader.read_bits(32).ok_or_else(missing)?;
+    let reason_len = reader.read_bits(32).ok_or_else(missing)?;
+
+    let mut reason_bytes = Vec::with_capacity(reason_len as usize);
+    for _ in 0..reason_len {
+        reason_bytes.push(reader.read_bits(8).ok_or_else(missing)? as u8);
+    }
+    let lose_reason = String::from_utf8_lossy(&reason_bytes).to_string();
+
+    let result = GameResult {
+        seed: header.seed,
+        winner,
+        tick,
+        cycle,
+        lose_reason,
+    };
+
+    Ok((header, result))
+}
+
+/// Decodes a replay file's per-tick frames. Always empty until the engine exports per-tick
+/// board state (see module docs).
+pub fn read_frames(path: &Path) -> io::Result<Vec<BoardSnapshot>> {
+    let _ = read_header_and_result(path)?;
+    Ok(Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bit_packed_writer_reader_round_trip_sub_byte_fields() {
+        let mut writer = BitPackedWriter::new();
+        writer.write_bits(0b101, 3);
+        writer.write_bits(0xBEEF, 16);
+        writer.byte_align();
+        writer.write_bits(0xDEAD_BEEF, 32);
+        let bytes = writer.into_bytes();
+
+        let mut reader = BitPackedReader::new(&bytes);
+        assert_eq!(reader.read_bits(3), Some(0b101));
+        assert_eq!(reader.read_bits(16), Some(0xBEEF));
+        reader.byte_align();
+        assert_eq!(reader.read_bits(32), Some(0xDEAD_BEEF));
+    }
+
+    #[test]
+    fn bit_packed_reader_returns_none_past_the_end_of_the_buffer() {
+        let mut writer = BitPackedWriter::new();
+        writer.write_bits(0b1, 1);
+        let bytes = writer.into_bytes();
+
+        let mut reader = BitPackedReader::new(&bytes);
+        assert_eq!(reader.read_bits(8), Some(0b10000000));
+        assert_eq!(reader.read_bits(8), None);
+    }
+
+    #[test]
+    fn write_replay_then_read_header_and_result_round_trips() {
+        let path = std::env::temp_dir().join("snake_runtime_replay_roundtrip_test.replay");
+        let header = ReplayHeader {
+            width: 20,
+            height: 20,
+            seed: 42,
+            tick_count: 137,
+        };
+        let result = GameResult {
+            seed: 42,
+            winner: Winner::Blue,
+            tick: 137,
+            cycle: 9001,
+            lose_reason: "RED: starved".to_string(),
+        };
+
+        write_replay(&path, header, &result).unwrap();
+        let (read_header, read_result) = read_header_and_result(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read_header, header);
+        assert_eq!(read_result.winner, result.winner);
+        assert_eq!(read_result.tick, result.tick);
+        assert_eq!(read_result.cycle, result.cycle);
+        assert_eq!(read_result.lose_reason, result.lose_reason);
+    }
+}
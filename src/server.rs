@@ -0,0 +1,373 @@
+//! HTTP match-server mode.
+//!
+//! Keeps an `Arc<Mutex<HashMap<Uuid, Match>>>` of in-flight and finished matches, the same
+//! shape a long-lived game server would use. A `POST /match` kicks off a threaded runner
+//! against a pair of uploaded WASM blobs, `GET /match/{id}` polls progress, and
+//! `GET /match/{id}/result` returns the final `JsonOutput` plus the lose-reason tables once the
+//! match is done. This lets a web frontend submit players and stream standings without shelling
+//! out to the CLI binary.
+//!
+//! `CreateMatchRequest` mirrors the CLI's `max-memory-pages`/`max-code-size`/`required-export`/
+//! `fuel`/`engine-memory-pages` flags so an operator accepting arbitrary WASM over HTTP has the
+//! same validation and metering controls the CLI batch mode has, rather than always running
+//! under the library defaults.
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    extract::{Path, State as AxumState},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    metering,
+    validation::{self, RuleSet},
+    GameResult, JsonOutput, LoseReasonTable, SnakeRuntime, Winner,
+};
+
+/// Shared table of in-flight and completed matches, cloned into every request handler.
+pub type State = Arc<Mutex<HashMap<Uuid, Match>>>;
+
+/// A single match's progress, mutated by its worker threads as games finish.
+pub struct Match {
+    games: u32,
+    completed: u32,
+    wins: HashMap<Winner, u32>,
+    lose_reasons: LoseReasonTable,
+    done: bool,
+}
+
+impl Match {
+    fn new(games: u32) -> Self {
+        Match {
+            games,
+            completed: 0,
+            wins: HashMap::new(),
+            lose_reasons: HashMap::new(),
+            done: false,
+        }
+    }
+
+    fn record(&mut self, seed: u32, result: &GameResult) {
+        *self.wins.entry(result.winner).or_insert(0) += 1;
+
+        let examples = self
+            .lose_reasons
+            .entry(result.winner)
+            .or_default()
+            .entry(result.lose_reason.clone())
+            .or_default();
+        if examples.1.len() < 5 {
+            examples.1.push(seed);
+        }
+        examples.0 += 1;
+
+        self.completed += 1;
+        if self.completed == self.games {
+            self.done = true;
+        }
+    }
+}
+
+/// Body of `POST /match`: the two player WASM blobs, the seed range to simulate, and the same
+/// validation/metering knobs the CLI batch mode exposes as flags.
+#[derive(Deserialize)]
+pub struct CreateMatchRequest {
+    pub red: Vec<u8>,
+    pub blue: Vec<u8>,
+    pub seed: u32,
+    pub games: u32,
+    #[serde(default)]
+    pub threads: Option<usize>,
+    /// Instruction budget per game. Defaults to [`crate::DEFAULT_FUEL`].
+    #[serde(default = "default_fuel")]
+    pub fuel: u64,
+    /// Maximum linear-memory pages (64 KiB each) a player module may declare, checked statically
+    /// against the module alone — not a runtime cap (see `engine_memory_pages` for that).
+    /// Defaults to [`validation::DEFAULT_MAX_MEMORY_PAGES`].
+    #[serde(default = "default_max_memory_pages")]
+    pub max_memory_pages: u32,
+    /// Maximum player module size, in bytes. Defaults to [`validation::DEFAULT_MAX_CODE_SIZE`].
+    #[serde(default = "default_max_code_size")]
+    pub max_code_size: usize,
+    /// Exports a player module must provide to be accepted.
+    #[serde(default)]
+    pub required_exports: Vec<String>,
+    /// Linear-memory pages (64 KiB each) available to the shared host engine instance at
+    /// runtime, which holds both players' raw bytes plus the engine's own game-state working
+    /// set. Unrelated to `max_memory_pages`; raise this if `max_code_size` is raised well past
+    /// its default. Defaults to [`metering::DEFAULT_ENGINE_MEMORY_PAGES`].
+    #[serde(default = "default_engine_memory_pages")]
+    pub engine_memory_pages: u32,
+}
+
+fn default_fuel() -> u64 {
+    crate::DEFAULT_FUEL
+}
+
+fn default_max_memory_pages() -> u32 {
+    validation::DEFAULT_MAX_MEMORY_PAGES
+}
+
+fn default_max_code_size() -> usize {
+    validation::DEFAULT_MAX_CODE_SIZE
+}
+
+fn default_engine_memory_pages() -> u32 {
+    metering::DEFAULT_ENGINE_MEMORY_PAGES
+}
+
+#[derive(Serialize)]
+pub struct CreateMatchResponse {
+    pub id: Uuid,
+}
+
+/// Response of `GET /match/{id}`: how far the match has gotten.
+#[derive(Serialize)]
+pub struct MatchProgress {
+    pub completed: u32,
+    pub games: u32,
+    pub wins: HashMap<String, u32>,
+    pub done: bool,
+}
+
+/// Response of `GET /match/{id}/result`: the final tallies and why each side lost.
+#[derive(Serialize)]
+pub struct MatchResultResponse {
+    pub result: JsonOutput,
+    pub lose_reasons: HashMap<String, HashMap<String, (u32, Vec<u32>)>>,
+}
+
+/// Starts the HTTP match server and serves requests on `addr` until the process exits.
+pub async fn serve(addr: SocketAddr) {
+    let state: State = Arc::new(Mutex::new(HashMap::new()));
+
+    let app = Router::new()
+        .route("/match", post(create_match))
+        .route("/match/:id", get(get_match))
+        .route("/match/:id/result", get(get_match_result))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    axum::serve(listener, app).await.unwrap();
+}
+
+async fn create_match(
+    AxumState(state): AxumState<State>,
+    Json(req): Json<CreateMatchRequest>,
+) -> Result<Json<CreateMatchResponse>, (StatusCode, String)> {
+    // Checked up front so a seed range that overflows a u32 is rejected with a clear reason
+    // instead of panicking (or silently wrapping) inside a spawned worker thread, which would
+    // otherwise leave the match stuck at `done: false` forever.
+    let end_seed = req.seed.checked_add(req.games).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!(
+                "seed {} + games {} overflows a u32; lower one of them",
+                req.seed, req.games
+            ),
+        )
+    })?;
+
+    let rule_set = RuleSet::standard(
+        req.max_memory_pages,
+        req.max_code_size,
+        req.required_exports.clone(),
+    );
+
+    // Validate once up front so a malformed submission is rejected with a clear reason
+    // instead of silently failing inside a spawned worker thread.
+    if let Err(violations) = SnakeRuntime::with_limits(
+        &req.red,
+        &req.blue,
+        &rule_set,
+        req.fuel,
+        req.engine_memory_pages,
+    ) {
+        return Err((StatusCode::BAD_REQUEST, violations.to_string()));
+    }
+
+    let id = Uuid::new_v4();
+    state.lock().unwrap().insert(id, Match::new(req.games));
+
+    let num_threads = req.threads.unwrap_or_else(|| {
+        std::thread::available_parallelism()
+            .map(NonZeroUsize::get)
+            .unwrap_or(1)
+    });
+
+    spawn_match_workers(id, req, end_seed, num_threads, state);
+
+    Ok(Json(CreateMatchResponse { id }))
+}
+
+/// Spawns `num_threads` worker threads, each with its own `SnakeRuntime`, that pull seeds from a
+/// shared counter and record results into the shared `State`. `end_seed` is the exclusive end
+/// of the seed range, already checked for overflow by `create_match`.
+fn spawn_match_workers(id: Uuid, req: CreateMatchRequest, end_seed: u32, num_threads: usize, state: State) {
+    let next_seed = Arc::new(Mutex::new(req.seed));
+
+    for _ in 0..num_threads {
+        let red = req.red.clone();
+        let blue = req.blue.clone();
+        let state = state.clone();
+        let next_seed = next_seed.clone();
+        let rule_set = RuleSet::standard(
+            req.max_memory_pages,
+            req.max_code_size,
+            req.required_exports.clone(),
+        );
+
+        std::thread::spawn(move || {
+            let mut runtime = SnakeRuntime::with_limits(
+                &red,
+                &blue,
+                &rule_set,
+                req.fuel,
+                req.engine_memory_pages,
+            )
+            .expect("player modules were validated in create_match");
+
+            loop {
+                let seed = {
+                    let mut next_seed = next_seed.lock().unwrap();
+                    if *next_seed >= end_seed {
+                        return;
+                    }
+                    let seed = *next_seed;
+                    *next_seed += 1;
+                    seed
+                };
+
+                let result = runtime.run_game(seed);
+
+                let mut matches = state.lock().unwrap();
+                if let Some(m) = matches.get_mut(&id) {
+                    m.record(seed, &result);
+                }
+            }
+        });
+    }
+}
+
+async fn get_match(
+    AxumState(state): AxumState<State>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<MatchProgress>, StatusCode> {
+    let matches = state.lock().unwrap();
+    let m = matches.get(&id).ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(MatchProgress {
+        completed: m.completed,
+        games: m.games,
+        wins: m
+            .wins
+            .iter()
+            .map(|(winner, count)| (format!("{winner:?}"), *count))
+            .collect(),
+        done: m.done,
+    }))
+}
+
+async fn get_match_result(
+    AxumState(state): AxumState<State>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<MatchResultResponse>, StatusCode> {
+    let matches = state.lock().unwrap();
+    let m = matches.get(&id).ok_or(StatusCode::NOT_FOUND)?;
+
+    if !m.done {
+        return Err(StatusCode::ACCEPTED);
+    }
+
+    let result = JsonOutput {
+        red: m.wins.get(&Winner::Red).copied().unwrap_or(0),
+        tie: m.wins.get(&Winner::Tie).copied().unwrap_or(0),
+        blue: m.wins.get(&Winner::Blue).copied().unwrap_or(0),
+    };
+
+    let lose_reasons = m
+        .lose_reasons
+        .iter()
+        .map(|(winner, reasons)| (format!("{winner:?}"), reasons.clone()))
+        .collect();
+
+    Ok(Json(MatchResultResponse {
+        result,
+        lose_reasons,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(winner: Winner, lose_reason: &str) -> GameResult {
+        GameResult {
+            seed: 0,
+            winner,
+            tick: 1,
+            cycle: 1,
+            lose_reason: lose_reason.to_string(),
+        }
+    }
+
+    #[test]
+    fn match_new_starts_not_done_with_nothing_recorded() {
+        let m = Match::new(3);
+        assert_eq!(m.completed, 0);
+        assert!(!m.done);
+    }
+
+    #[test]
+    fn match_record_tallies_wins_and_marks_done_once_every_game_is_in() {
+        let mut m = Match::new(2);
+        m.record(0, &result(Winner::Red, "BLUE: starved"));
+        assert!(!m.done);
+        m.record(1, &result(Winner::Blue, "RED: starved"));
+
+        assert_eq!(m.completed, 2);
+        assert!(m.done);
+        assert_eq!(*m.wins.get(&Winner::Red).unwrap(), 1);
+        assert_eq!(*m.wins.get(&Winner::Blue).unwrap(), 1);
+    }
+
+    #[test]
+    fn match_record_caps_example_seeds_at_five_per_lose_reason() {
+        let mut m = Match::new(10);
+        for seed in 0..10 {
+            m.record(seed, &result(Winner::Red, "BLUE: starved"));
+        }
+
+        let (count, examples) = m.lose_reasons[&Winner::Red]["BLUE: starved"].clone();
+        assert_eq!(count, 10);
+        assert_eq!(examples, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn create_match_request_fills_in_defaults_for_omitted_fields() {
+        let req: CreateMatchRequest = serde_json::from_str(
+            r#"{"red": [0], "blue": [0], "seed": 0, "games": 1}"#,
+        )
+        .unwrap();
+
+        assert_eq!(req.fuel, crate::DEFAULT_FUEL);
+        assert_eq!(req.max_memory_pages, validation::DEFAULT_MAX_MEMORY_PAGES);
+        assert_eq!(req.max_code_size, validation::DEFAULT_MAX_CODE_SIZE);
+        assert_eq!(
+            req.engine_memory_pages,
+            metering::DEFAULT_ENGINE_MEMORY_PAGES
+        );
+        assert!(req.required_exports.is_empty());
+        assert!(req.threads.is_none());
+    }
+}
@@ -0,0 +1,305 @@
+//! Static validation of a player's WASM module before any game runs.
+//!
+//! Previously the only check was a runtime winner code (3/4) that `panic!`ed mid-batch with
+//! "RED/BLUE WASM failed validation" once the engine itself noticed a malformed player. This
+//! module screens each player module up front instead: a `RuleSet` (analogous to a lint rule
+//! runner) runs every configured `Rule` against the compiled `Module` and the raw bytes, and
+//! `SnakeRuntime::new` aggregates every failure into a `ValidationError` rather than crashing a
+//! worker thread on the first bad submission. This is a host-side static screen, though — it
+//! can't prove the engine's own runtime winner-3/4 check is unreachable, since it never actually
+//! runs the player. `SnakeRuntime::run_game` backstops that: a winner-3/4 signal that slips past
+//! every `Rule` still turns into a `GameResult` (the offending team loses) instead of a panic.
+
+use wasmer::{wasmparser, Module};
+
+/// Default cap (64 KiB pages) on the linear memory a player module may declare, used by
+/// [`RuleSet::default`]. The runtime cap on the host engine instance is separate:
+/// `metering::DEFAULT_ENGINE_MEMORY_PAGES`.
+pub const DEFAULT_MAX_MEMORY_PAGES: u32 = 16;
+
+/// Default module size cap, in bytes, used by [`RuleSet::default`].
+pub const DEFAULT_MAX_CODE_SIZE: usize = 1_048_576;
+
+/// One static check a player module must pass.
+pub trait Rule {
+    /// Short, stable name used to label violations (e.g. in CLI output).
+    fn name(&self) -> &str;
+
+    /// Checks `module`/`wasm_bytes`, returning the violation if this rule fails.
+    fn check(&self, wasm_bytes: &[u8], module: &Module) -> Result<(), Violation>;
+}
+
+/// Why a single rule rejected a module.
+#[derive(Debug, Clone)]
+pub struct Violation {
+    pub team: String,
+    pub rule: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} ({})", self.team, self.reason, self.rule)
+    }
+}
+
+/// Every violation found while validating a pair of player modules.
+#[derive(Debug, Clone)]
+pub struct ValidationError {
+    pub violations: Vec<Violation>,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for violation in &self.violations {
+            writeln!(f, "{violation}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// A configurable collection of `Rule`s, run as one pass over a module.
+pub struct RuleSet {
+    rules: Vec<Box<dyn Rule>>,
+}
+
+impl RuleSet {
+    pub fn new() -> Self {
+        RuleSet { rules: Vec::new() }
+    }
+
+    pub fn with_rule(mut self, rule: impl Rule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// The standard rules, with the limits the CLI flags tune: a declared-memory cap, no
+    /// imports granted, the given required exports, and a code-size cap. [`RuleSet::default`]
+    /// is this set with the default limits and no required exports.
+    pub fn standard(max_memory_pages: u32, max_code_size: usize, required_exports: Vec<String>) -> Self {
+        RuleSet::new()
+            .with_rule(MaxMemoryPages(max_memory_pages))
+            .with_rule(NoImports)
+            .with_rule(RequiredExports(required_exports))
+            .with_rule(MaxCodeSize(max_code_size))
+    }
+
+    /// Runs every rule against `wasm_bytes`/`module`, tagging any violations with `team`.
+    pub fn check(&self, team: &str, wasm_bytes: &[u8], module: &Module) -> Vec<Violation> {
+        self.rules
+            .iter()
+            .filter_map(|rule| rule.check(wasm_bytes, module).err())
+            .map(|mut violation| {
+                violation.team = team.to_string();
+                violation
+            })
+            .collect()
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet::standard(DEFAULT_MAX_MEMORY_PAGES, DEFAULT_MAX_CODE_SIZE, Vec::new())
+    }
+}
+
+/// Rejects a module whose declared linear memory exceeds `self.0` pages (64 KiB each).
+pub struct MaxMemoryPages(pub u32);
+
+impl Rule for MaxMemoryPages {
+    fn name(&self) -> &str {
+        "max-memory-pages"
+    }
+
+    /// Parses `wasm_bytes`' memory section directly with `wasmparser` (the same crate
+    /// `metering`'s `cost_function` uses, re-exported as `wasmer::wasmparser`) instead of going
+    /// through `module.exports()` — a module can declare a memory without exporting it, which
+    /// would otherwise let it slip past this rule entirely.
+    fn check(&self, wasm_bytes: &[u8], _module: &Module) -> Result<(), Violation> {
+        for (index, pages) in declared_memory_pages(wasm_bytes).enumerate() {
+            if pages > self.0 as u64 {
+                return Err(Violation {
+                    team: String::new(),
+                    rule: self.name().to_string(),
+                    reason: format!(
+                        "memory #{index} declares {pages} pages, limit is {}",
+                        self.0
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Yields the initial page count of every memory `wasm_bytes` declares, exported or not.
+fn declared_memory_pages(wasm_bytes: &[u8]) -> impl Iterator<Item = u64> + '_ {
+    wasmparser::Parser::new(0)
+        .parse_all(wasm_bytes)
+        .filter_map(Result::ok)
+        .filter_map(|payload| match payload {
+            wasmparser::Payload::MemorySection(reader) => Some(reader),
+            _ => None,
+        })
+        .flat_map(|reader| reader.into_iter().filter_map(Result::ok))
+        .map(|memory_ty| memory_ty.initial)
+}
+
+/// Rejects any module that imports anything, since the host grants no imports today.
+pub struct NoImports;
+
+impl Rule for NoImports {
+    fn name(&self) -> &str {
+        "no-imports"
+    }
+
+    fn check(&self, _wasm_bytes: &[u8], module: &Module) -> Result<(), Violation> {
+        if let Some(import) = module.imports().next() {
+            return Err(Violation {
+                team: String::new(),
+                rule: self.name().to_string(),
+                reason: format!(
+                    "imports '{}.{}', but the host grants no imports",
+                    import.module(),
+                    import.name()
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Rejects a module missing one of a configured list of required exports.
+pub struct RequiredExports(pub Vec<String>);
+
+impl Rule for RequiredExports {
+    fn name(&self) -> &str {
+        "required-exports"
+    }
+
+    fn check(&self, _wasm_bytes: &[u8], module: &Module) -> Result<(), Violation> {
+        let exported: std::collections::HashSet<&str> =
+            module.exports().map(|export| export.name()).collect();
+
+        let missing: Vec<&str> = self
+            .0
+            .iter()
+            .map(String::as_str)
+            .filter(|name| !exported.contains(name))
+            .collect();
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(Violation {
+                team: String::new(),
+                rule: self.name().to_string(),
+                reason: format!("missing required export(s): {}", missing.join(", ")),
+            })
+        }
+    }
+}
+
+/// Rejects a module larger than `self.0` bytes.
+pub struct MaxCodeSize(pub usize);
+
+impl Rule for MaxCodeSize {
+    fn name(&self) -> &str {
+        "max-code-size"
+    }
+
+    fn check(&self, wasm_bytes: &[u8], _module: &Module) -> Result<(), Violation> {
+        if wasm_bytes.len() > self.0 {
+            Err(Violation {
+                team: String::new(),
+                rule: self.name().to_string(),
+                reason: format!("module is {} bytes, limit is {}", wasm_bytes.len(), self.0),
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasmer::Store;
+
+    fn compile(wat: &str) -> (Vec<u8>, Module) {
+        let store = Store::default();
+        // The rules read the binary encoding, so hand them the assembled module, not the text.
+        let bytes = wasmer::wat2wasm(wat.as_bytes())
+            .expect("fixture wat should assemble")
+            .into_owned();
+        let module = Module::new(&store, &bytes).expect("fixture wat should compile");
+        (bytes, module)
+    }
+
+    #[test]
+    fn no_imports_rejects_a_module_that_imports_anything() {
+        let (bytes, module) = compile(r#"(module (import "env" "log" (func)))"#);
+        assert!(NoImports.check(&bytes, &module).is_err());
+    }
+
+    #[test]
+    fn no_imports_accepts_a_module_with_no_imports() {
+        let (bytes, module) = compile(r#"(module (func))"#);
+        assert!(NoImports.check(&bytes, &module).is_ok());
+    }
+
+    #[test]
+    fn max_memory_pages_rejects_memory_over_the_limit() {
+        let (bytes, module) = compile(r#"(module (memory (export "memory") 2))"#);
+        assert!(MaxMemoryPages(1).check(&bytes, &module).is_err());
+    }
+
+    #[test]
+    fn max_memory_pages_accepts_memory_within_the_limit() {
+        let (bytes, module) = compile(r#"(module (memory (export "memory") 1))"#);
+        assert!(MaxMemoryPages(1).check(&bytes, &module).is_ok());
+    }
+
+    #[test]
+    fn max_memory_pages_rejects_memory_that_is_declared_but_not_exported() {
+        let (bytes, module) = compile(r#"(module (memory 2))"#);
+        assert!(MaxMemoryPages(1).check(&bytes, &module).is_err());
+    }
+
+    #[test]
+    fn required_exports_rejects_a_module_missing_one() {
+        let (bytes, module) = compile(r#"(module (func (export "run_game")))"#);
+        let rule = RequiredExports(vec!["run_game".to_string(), "allocate_bytes".to_string()]);
+        assert!(rule.check(&bytes, &module).is_err());
+    }
+
+    #[test]
+    fn required_exports_accepts_a_module_with_every_export() {
+        let (bytes, module) = compile(r#"(module (func (export "run_game")))"#);
+        let rule = RequiredExports(vec!["run_game".to_string()]);
+        assert!(rule.check(&bytes, &module).is_ok());
+    }
+
+    #[test]
+    fn max_code_size_rejects_a_module_over_the_byte_limit() {
+        let (bytes, module) = compile(r#"(module (func))"#);
+        assert!(MaxCodeSize(bytes.len() - 1).check(&bytes, &module).is_err());
+    }
+
+    #[test]
+    fn max_code_size_accepts_a_module_within_the_byte_limit() {
+        let (bytes, module) = compile(r#"(module (func))"#);
+        assert!(MaxCodeSize(bytes.len()).check(&bytes, &module).is_ok());
+    }
+
+    #[test]
+    fn rule_set_check_tags_every_violation_with_the_team() {
+        let (bytes, module) = compile(r#"(module (import "env" "log" (func)))"#);
+        let rule_set = RuleSet::new().with_rule(NoImports);
+        let violations = rule_set.check("RED", &bytes, &module);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].team, "RED");
+    }
+}